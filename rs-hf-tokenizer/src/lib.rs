@@ -1,14 +1,95 @@
-use jni::objects::{JByteArray, JClass, JString, ReleaseMode};
-use jni::sys::{jbyteArray, jlong};
+use jni::objects::{JByteArray, JClass, JIntArray, JObjectArray, JString, ReleaseMode};
+use jni::sys::{jboolean, jbyteArray, jint, jlong};
 use jni::JNIEnv;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
-use tokenizers::Tokenizer;
+use tokenizers::{AddedToken, PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+const TOKENIZER_EXCEPTION_CLASS: &str = "com/whispercppdemo/intent/TokenizerException";
+
+/// `strategy` values accepted by `createTokenizerWithConfig`.
+const STRATEGY_NONE: jint = 0;
+const STRATEGY_FIXED_LENGTH: jint = 1;
+const STRATEGY_LONGEST_IN_BATCH: jint = 2;
+
+/// Mirrors the `special_token_map.json` shape rust-bert loads alongside `tokenizer.json`: a flat
+/// map of special-token role to literal token string. All fields are optional since not every
+/// tokenizer defines every role.
+#[derive(Deserialize, Default)]
+struct SpecialTokenMap {
+    unk_token: Option<String>,
+    sep_token: Option<String>,
+    pad_token: Option<String>,
+    cls_token: Option<String>,
+    mask_token: Option<String>,
+    bos_token: Option<String>,
+    eos_token: Option<String>,
+}
+
+impl SpecialTokenMap {
+    fn added_tokens(&self) -> Vec<AddedToken> {
+        [
+            &self.unk_token,
+            &self.sep_token,
+            &self.pad_token,
+            &self.cls_token,
+            &self.mask_token,
+            &self.bos_token,
+            &self.eos_token,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|token| AddedToken::from(token.clone(), true))
+        .collect()
+    }
+}
+
+fn read_byte_array(env: &mut JNIEnv, array: &JByteArray) -> Result<Vec<u8>, jni::errors::Error> {
+    unsafe {
+        Ok(env
+            .get_array_elements(array, ReleaseMode::CopyBack)?
+            .iter()
+            .map(|x| *x as u8)
+            .collect())
+    }
+}
 
 #[derive(Serialize)]
 struct TokenizationResult {
     ids: Vec<u32>,
     attention_mask: Vec<u32>,
+    offsets: Vec<(usize, usize)>,
+    word_ids: Vec<Option<u32>>,
+}
+
+#[derive(Serialize)]
+struct BatchTokenizationResult {
+    ids: Vec<Vec<u32>>,
+    attention_mask: Vec<Vec<u32>>,
+    offsets: Vec<Vec<(usize, usize)>>,
+    word_ids: Vec<Vec<Option<u32>>>,
+}
+
+/// Lets a `Result` be unwrapped across the JNI boundary by throwing a `TokenizerException`
+/// back into the JVM on `Err` instead of panicking, which would otherwise unwind through the
+/// FFI boundary and abort the whole process. Mirrors the `JExceptable` pattern codemp uses for
+/// its own JNI bridge: the caller provides the sentinel value to return once the exception has
+/// been raised, since the native method still has to return *something* before control passes
+/// back to Java and the pending exception is thrown there.
+trait JExceptable<T> {
+    fn jexcept(self, env: &mut JNIEnv, sentinel: T) -> T;
+}
+
+impl<T, E: std::fmt::Display> JExceptable<T> for Result<T, E> {
+    fn jexcept(self, env: &mut JNIEnv, sentinel: T) -> T {
+        match self {
+            Ok(value) => value,
+            Err(err) => {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                sentinel
+            }
+        }
+    }
 }
 
 #[no_mangle]
@@ -17,21 +98,147 @@ pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_createTokenizer<'a>
     _: JClass<'a>,
     tokenizer_bytes: JByteArray<'a>,
 ) -> jlong {
-    unsafe {
-        let tokenizer_bytes_rs: Vec<u8> = env
-            .get_array_elements(&tokenizer_bytes, ReleaseMode::CopyBack)
-            .expect("Could not read tokenizer_bytes")
-            .iter()
-            .map(|x| *x as u8)
-            .collect();
-        match Tokenizer::from_bytes(&tokenizer_bytes_rs) {
-            Ok(tokenizer) => Box::into_raw(Box::new(tokenizer)) as jlong,
-            Err(_) => {
-                // Return null pointer on error
-                0
+    let tokenizer_bytes_rs = match read_byte_array(&mut env, &tokenizer_bytes) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return 0;
+        }
+    };
+
+    match Tokenizer::from_bytes(&tokenizer_bytes_rs) {
+        Ok(tokenizer) => Box::into_raw(Box::new(tokenizer)) as jlong,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_createTokenizerWithConfig<'a>(
+    mut env: JNIEnv<'a>,
+    _: JClass<'a>,
+    tokenizer_bytes: JByteArray<'a>,
+    special_token_map_bytes: JByteArray<'a>,
+    max_length: jint,
+    strategy: jint,
+) -> jlong {
+    let tokenizer_bytes_rs = match read_byte_array(&mut env, &tokenizer_bytes) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return 0;
+        }
+    };
+
+    let mut tokenizer = match Tokenizer::from_bytes(&tokenizer_bytes_rs) {
+        Ok(tokenizer) => tokenizer,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return 0;
+        }
+    };
+
+    let mut pad_token: Option<String> = None;
+    if !special_token_map_bytes.is_null() {
+        let special_token_map_bytes_rs =
+            match read_byte_array(&mut env, &special_token_map_bytes) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                    return 0;
+                }
+            };
+        let special_token_map: SpecialTokenMap =
+            match serde_json::from_slice(&special_token_map_bytes_rs) {
+                Ok(map) => map,
+                Err(err) => {
+                    let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                    return 0;
+                }
+            };
+        tokenizer.add_special_tokens(&special_token_map.added_tokens());
+        pad_token = special_token_map.pad_token;
+    }
+
+    // Resolve the merged pad token to its id up front so every padding strategy below pads with
+    // the tokenizer's actual pad id/token instead of silently defaulting to id 0.
+    let pad_id = match &pad_token {
+        Some(token) => match tokenizer.token_to_id(token) {
+            Some(id) => Some(id),
+            None => {
+                let _ = env.throw_new(
+                    TOKENIZER_EXCEPTION_CLASS,
+                    format!("special token map's pad_token {token:?} was not found in the tokenizer vocabulary"),
+                );
+                return 0;
+            }
+        },
+        None => None,
+    };
+    let padding_params = |strategy: PaddingStrategy| -> PaddingParams {
+        let mut params = PaddingParams {
+            strategy,
+            ..Default::default()
+        };
+        if let (Some(id), Some(token)) = (pad_id, &pad_token) {
+            params.pad_id = id;
+            params.pad_token = token.clone();
+        }
+        params
+    };
+
+    match strategy {
+        STRATEGY_NONE => {
+            if let Err(err) = tokenizer.with_truncation(None) {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                return 0;
+            }
+            tokenizer.with_padding(None);
+        }
+        STRATEGY_FIXED_LENGTH => {
+            if max_length <= 0 {
+                let _ = env.throw_new(
+                    TOKENIZER_EXCEPTION_CLASS,
+                    "max_length must be greater than 0 for the fixed-length strategy",
+                );
+                return 0;
+            }
+            let max_length = max_length as usize;
+            let truncation = TruncationParams {
+                max_length,
+                ..Default::default()
+            };
+            if let Err(err) = tokenizer.with_truncation(Some(truncation)) {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                return 0;
             }
+            tokenizer.with_padding(Some(padding_params(PaddingStrategy::Fixed(max_length))));
+        }
+        STRATEGY_LONGEST_IN_BATCH => {
+            if max_length > 0 {
+                let truncation = TruncationParams {
+                    max_length: max_length as usize,
+                    ..Default::default()
+                };
+                if let Err(err) = tokenizer.with_truncation(Some(truncation)) {
+                    let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                    return 0;
+                }
+            }
+            tokenizer.with_padding(Some(padding_params(PaddingStrategy::BatchLongest)));
+        }
+        other => {
+            let _ = env.throw_new(
+                TOKENIZER_EXCEPTION_CLASS,
+                format!("unknown truncation/padding strategy: {other}"),
+            );
+            return 0;
         }
     }
+
+    Box::into_raw(Box::new(tokenizer)) as jlong
 }
 
 #[no_mangle]
@@ -42,31 +249,158 @@ pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_tokenize<'a>(
     text: JString<'a>,
 ) -> JString<'a> {
     if tokenizer_ptr == 0 {
-        return env.new_string("{}").expect("Could not create empty JSON string");
+        let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, "tokenizer pointer is null");
+        return env.new_string("").unwrap_or_default();
     }
-    
+
     let tokenizer = unsafe { &mut *(tokenizer_ptr as *mut Tokenizer) };
-    let text: String = env
-        .get_string(&text)
-        .expect("Could not convert text to Rust String")
-        .into();
-    
-    match tokenizer.encode(text, true) {
-        Ok(encoding) => {
-            let result = TokenizationResult {
-                ids: encoding.get_ids().to_vec(),
-                attention_mask: encoding.get_attention_mask().to_vec(),
-            };
-            let result_json_str = serde_json::to_string(&result)
-                .expect("Could not convert tokenization result to JSON");
-            env.new_string(result_json_str)
-                .expect("Could not convert result_json_str to jstring")
+    let text: String = match env.get_string(&text) {
+        Ok(s) => s.into(),
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return env.new_string("").unwrap_or_default();
         }
-        Err(_) => {
-            env.new_string("{\"ids\": [], \"attention_mask\": []}")
-                .expect("Could not create error JSON string")
+    };
+
+    let encoding = match tokenizer.encode(text, true) {
+        Ok(encoding) => encoding,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return env.new_string("").unwrap_or_default();
         }
+    };
+
+    let result = TokenizationResult {
+        ids: encoding.get_ids().to_vec(),
+        attention_mask: encoding.get_attention_mask().to_vec(),
+        offsets: encoding.get_offsets().to_vec(),
+        word_ids: encoding.get_word_ids().to_vec(),
+    };
+    let result_json_str = serde_json::to_string(&result).jexcept(&mut env, String::new());
+    env.new_string(result_json_str).unwrap_or_default()
+}
+
+#[no_mangle]
+pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_tokenizeBatch<'a>(
+    mut env: JNIEnv<'a>,
+    _: JClass<'a>,
+    tokenizer_ptr: jlong,
+    texts: JObjectArray<'a>,
+    max_length: jint,
+) -> JString<'a> {
+    if tokenizer_ptr == 0 {
+        let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, "tokenizer pointer is null");
+        return env.new_string("").unwrap_or_default();
     }
+
+    let tokenizer = unsafe { &mut *(tokenizer_ptr as *mut Tokenizer) };
+
+    let len = match env.get_array_length(&texts) {
+        Ok(len) => len,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return env.new_string("").unwrap_or_default();
+        }
+    };
+
+    let mut texts_rs: Vec<String> = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let jtext: JString = match env.get_object_array_element(&texts, i) {
+            Ok(element) => element.into(),
+            Err(err) => {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                return env.new_string("").unwrap_or_default();
+            }
+        };
+        let text: String = match env.get_string(&jtext) {
+            Ok(s) => s.into(),
+            Err(err) => {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                return env.new_string("").unwrap_or_default();
+            }
+        };
+        texts_rs.push(text);
+    }
+
+    // Route padding/truncation through the tokenizer itself (same machinery `tokenize` and
+    // `createTokenizerWithConfig`'s FIXED_LENGTH strategy use) rather than hand-rolling it with
+    // `Vec::resize`, which would naively tail-chop overlong sequences. Padding uses the
+    // tokenizer's configured pad id/token; when it has no padding configured, the
+    // `PaddingParams` defaults (pad id 0, token "[PAD]") apply.
+    let original_truncation = tokenizer.get_truncation().cloned();
+    let original_padding = tokenizer.get_padding().cloned();
+
+    let padding_strategy = if max_length > 0 {
+        let truncation = TruncationParams {
+            max_length: max_length as usize,
+            ..original_truncation.clone().unwrap_or_default()
+        };
+        if let Err(err) = tokenizer.with_truncation(Some(truncation)) {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            return env.new_string("").unwrap_or_default();
+        }
+        PaddingStrategy::Fixed(max_length as usize)
+    } else {
+        PaddingStrategy::BatchLongest
+    };
+    let mut padding = original_padding.clone().unwrap_or_default();
+    padding.strategy = padding_strategy;
+    tokenizer.with_padding(Some(padding));
+
+    let encodings = match tokenizer.encode_batch(texts_rs, true) {
+        Ok(encodings) => encodings,
+        Err(err) => {
+            let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+            tokenizer.with_padding(original_padding);
+            let _ = tokenizer.with_truncation(original_truncation);
+            return env.new_string("").unwrap_or_default();
+        }
+    };
+
+    let result = BatchTokenizationResult {
+        ids: encodings.iter().map(|e| e.get_ids().to_vec()).collect(),
+        attention_mask: encodings.iter().map(|e| e.get_attention_mask().to_vec()).collect(),
+        offsets: encodings.iter().map(|e| e.get_offsets().to_vec()).collect(),
+        word_ids: encodings.iter().map(|e| e.get_word_ids().to_vec()).collect(),
+    };
+
+    // Restore whatever truncation/padding config the tokenizer had before this call so
+    // `tokenize`/`decode` on the same pointer aren't left permanently affected by a one-off
+    // `max_length` passed here.
+    tokenizer.with_padding(original_padding);
+    let _ = tokenizer.with_truncation(original_truncation);
+    let result_json_str = serde_json::to_string(&result).jexcept(&mut env, String::new());
+    env.new_string(result_json_str).unwrap_or_default()
+}
+
+#[no_mangle]
+pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_decode<'a>(
+    mut env: JNIEnv<'a>,
+    _: JClass<'a>,
+    tokenizer_ptr: jlong,
+    ids: JIntArray<'a>,
+    skip_special_tokens: jboolean,
+) -> JString<'a> {
+    if tokenizer_ptr == 0 {
+        let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, "tokenizer pointer is null");
+        return env.new_string("").unwrap_or_default();
+    }
+
+    let tokenizer = unsafe { &mut *(tokenizer_ptr as *mut Tokenizer) };
+    let ids_rs: Vec<u32> = unsafe {
+        match env.get_array_elements(&ids, ReleaseMode::NoCopyBack) {
+            Ok(elements) => elements.iter().map(|x| *x as u32).collect(),
+            Err(err) => {
+                let _ = env.throw_new(TOKENIZER_EXCEPTION_CLASS, err.to_string());
+                return env.new_string("").unwrap_or_default();
+            }
+        }
+    };
+
+    let text = tokenizer
+        .decode(&ids_rs, skip_special_tokens != 0)
+        .jexcept(&mut env, String::new());
+    env.new_string(text).unwrap_or_default()
 }
 
 #[no_mangle]
@@ -79,4 +413,4 @@ pub extern "C" fn Java_com_whispercppdemo_intent_HFTokenizer_deleteTokenizer(
         let _ptr = unsafe { Box::from_raw(tokenizer_ptr as *mut Tokenizer) };
         // _ptr will be automatically deallocated when it goes out of scope
     }
-}
\ No newline at end of file
+}